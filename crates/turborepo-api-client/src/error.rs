@@ -0,0 +1,37 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    ReqwestError(#[from] reqwest::Error),
+    /// Returned when a downloaded artifact's recomputed HMAC tag doesn't
+    /// match the `x-artifact-tag` the server sent, meaning the artifact was
+    /// corrupted or tampered with in transit.
+    #[error("artifact tag mismatch: expected {expected}, got {actual}")]
+    ArtifactTagMismatch { expected: String, actual: String },
+    /// A signing key is configured but the server sent no `x-artifact-tag`
+    /// header to verify against. Treated as a verification failure rather
+    /// than silently skipped, since an attacker able to strip one response
+    /// header would otherwise bypass HMAC verification entirely.
+    #[error("artifact {hash} is missing its x-artifact-tag header and cannot be verified")]
+    ArtifactTagMissing { hash: String },
+    /// `ensure_token` needed a new token but has no client credentials to
+    /// refresh with: `authenticate` never succeeded, and any token from
+    /// `authenticate_device` (which stores no credentials) is missing or
+    /// within 60s of expiring.
+    #[error("not authenticated: call authenticate() or authenticate_device() first")]
+    NotAuthenticated,
+    /// A retryable request never succeeded within its retry budget.
+    #[error("request failed after {attempts} attempt(s): {source}")]
+    RetriesExhausted {
+        attempts: u32,
+        #[source]
+        source: Box<Error>,
+    },
+    /// The final response in a retry loop was still transient (429/5xx)
+    /// after exhausting the retry budget. Wrapped in [`Error::RetriesExhausted`]
+    /// so exhaustion always surfaces the same way, whether the last attempt
+    /// failed outright or merely came back with a transient status.
+    #[error("server responded with transient status {status}")]
+    TransientStatus { status: reqwest::StatusCode },
+}
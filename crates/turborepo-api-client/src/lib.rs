@@ -1,15 +1,26 @@
 #![feature(async_closure)]
 
-use std::{env, future::Future};
+use std::{env, future::Future, sync::Mutex};
 
+use base64::Engine;
+use hmac::{Hmac, Mac};
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
+use sha2::Sha256;
 
 pub use crate::error::Error;
-use crate::retry::retry_future;
+pub use crate::retry::RetryConfig;
+pub use crate::token::AccessToken;
+use crate::{
+    rate_limit::{LimitType, RateLimiter},
+    retry::retry_future,
+    token::{AuthState, CachedToken},
+};
 
 mod error;
+mod rate_limit;
 mod retry;
+mod token;
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct VerifiedSsoUser {
@@ -110,12 +121,119 @@ pub struct APIClient {
     client: reqwest::Client,
     base_url: String,
     user_agent: String,
+    signing_key: Option<Vec<u8>>,
+    auth_state: Mutex<AuthState>,
+    rate_limiter: RateLimiter,
+    retry_config: RetryConfig,
 }
 
 impl APIClient {
+    /// OAuth2 client-credentials grant: exchanges `client_id`/`client_secret`
+    /// for an access token, caching it (and the credentials, for later
+    /// refresh) so `ensure_token` can hand it to the `_managed` request
+    /// methods below without the caller juggling tokens itself.
+    pub async fn authenticate(&self, client_id: &str, client_secret: &str) -> Result<(), Error> {
+        let access_token = self
+            .request_access_token(&[
+                ("grant_type", "client_credentials"),
+                ("client_id", client_id),
+                ("client_secret", client_secret),
+            ])
+            .await?;
+
+        let mut auth_state = self.auth_state.lock().unwrap();
+        auth_state.credentials = Some((client_id.to_owned(), client_secret.to_owned()));
+        auth_state.token = Some(CachedToken::from_access_token(access_token));
+
+        Ok(())
+    }
+
+    /// Device-authorization grant, for CLI logins that poll a device code
+    /// rather than holding a client secret.
+    pub async fn authenticate_device(&self, device_code: &str, client_id: &str) -> Result<(), Error> {
+        let access_token = self
+            .request_access_token(&[
+                ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+                ("device_code", device_code),
+                ("client_id", client_id),
+            ])
+            .await?;
+
+        self.auth_state.lock().unwrap().token = Some(CachedToken::from_access_token(access_token));
+
+        Ok(())
+    }
+
+    async fn request_access_token(&self, form: &[(&str, &str)]) -> Result<AccessToken, Error> {
+        let response = self
+            .make_retryable_request(LimitType::Auth, async || {
+                let request_builder = self
+                    .client
+                    .post(self.make_url("/oauth/token"))
+                    .header("User-Agent", self.user_agent.clone())
+                    .form(form);
+
+                Ok(request_builder.send().await?)
+            })
+            .await?
+            .error_for_status()?;
+
+        Ok(response.json().await?)
+    }
+
+    /// Returns a still-valid bearer token, transparently refreshing it via
+    /// `authenticate` when it's missing or within 60s of expiring. Requires
+    /// `authenticate` (or `authenticate_device`, for the initial token) to
+    /// have succeeded at least once.
+    async fn ensure_token(&self) -> Result<String, Error> {
+        let needs_refresh = self
+            .auth_state
+            .lock()
+            .unwrap()
+            .token
+            .as_ref()
+            .map(CachedToken::needs_refresh)
+            .unwrap_or(true);
+
+        if needs_refresh {
+            let (client_id, client_secret) = self
+                .auth_state
+                .lock()
+                .unwrap()
+                .credentials
+                .clone()
+                .ok_or(Error::NotAuthenticated)?;
+            self.authenticate(&client_id, &client_secret).await?;
+        }
+
+        Ok(self
+            .auth_state
+            .lock()
+            .unwrap()
+            .token
+            .as_ref()
+            .expect("authenticate always populates the cached token on success")
+            .access_token
+            .clone())
+    }
+
+    /// Ergonomic variant of [`Self::get_user`] that manages its own bearer
+    /// token via `ensure_token` instead of requiring the caller to supply
+    /// one.
+    pub async fn get_user_managed(&self) -> Result<UserResponse, Error> {
+        let token = self.ensure_token().await?;
+        self.get_user(&token).await
+    }
+
+    /// Ergonomic variant of [`Self::get_teams`]. See `get_user_managed`.
+    pub async fn get_teams_managed(&self) -> Result<TeamsResponse, Error> {
+        let token = self.ensure_token().await?;
+        self.get_teams(&token).await
+    }
+
     pub async fn get_user(&self, token: &str) -> Result<UserResponse, Error> {
         let response = self
-            .make_retryable_request(async || {
+            .make_retryable_request(LimitType::Global, async || {
                 let url = self.make_url("/v2/user");
                 let request_builder = self
                     .client
@@ -134,7 +252,7 @@ impl APIClient {
 
     pub async fn get_teams(&self, token: &str) -> Result<TeamsResponse, Error> {
         let response = self
-            .make_retryable_request(async || {
+            .make_retryable_request(LimitType::Global, async || {
                 let request_builder = self
                     .client
                     .get(self.make_url("/v2/teams?limit=100"))
@@ -172,7 +290,7 @@ impl APIClient {
         team_slug: Option<&str>,
     ) -> Result<CachingStatusResponse, Error> {
         let response = self
-            .make_retryable_request(async || {
+            .make_retryable_request(LimitType::Artifact, async || {
                 let mut request_builder = self
                     .client
                     .get(self.make_url("/v8/artifacts/status"))
@@ -201,7 +319,7 @@ impl APIClient {
         token_name: &str,
     ) -> Result<VerifiedSsoUser, Error> {
         let response = self
-            .make_retryable_request(async || {
+            .make_retryable_request(LimitType::Auth, async || {
                 let request_builder = self
                     .client
                     .get(self.make_url("/registration/verify"))
@@ -221,19 +339,157 @@ impl APIClient {
         })
     }
 
-    pub async fn fetch_artifact(&self, hash: &str) -> Result<ArtifactResponse, Error> {
-        todo!()
+    /// Downloads the cached artifact for `hash`. When a signing key has been
+    /// configured via [`Self::with_signing_key`], recomputes the HMAC tag
+    /// over the received body and compares it in constant time against the
+    /// server's `x-artifact-tag` header, returning
+    /// [`Error::ArtifactTagMismatch`] if they differ, or
+    /// [`Error::ArtifactTagMissing`] if the header is absent entirely - a
+    /// missing header is not treated as "nothing to check". Without a
+    /// signing key, verification is skipped and the tag is only carried
+    /// through on [`ArtifactResponse::expected_tag`] for the caller's own
+    /// use.
+    pub async fn fetch_artifact(
+        &self,
+        hash: &str,
+        token: &str,
+        team_id: &str,
+    ) -> Result<ArtifactResponse, Error> {
+        let response = self
+            .make_retryable_request(LimitType::Artifact, async || {
+                let request_builder = self
+                    .client
+                    .get(self.make_url(&format!("/v8/artifacts/{}", hash)))
+                    .query(&[("teamId", team_id)])
+                    .header("User-Agent", self.user_agent.clone())
+                    .header("Authorization", format!("Bearer {}", token));
+
+                Ok(request_builder.send().await?)
+            })
+            .await?
+            .error_for_status()?;
+
+        let expected_tag = response
+            .headers()
+            .get("x-artifact-tag")
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_owned());
+        let duration = response
+            .headers()
+            .get("x-artifact-duration")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(0);
+
+        let body = response.bytes().await?.to_vec();
+
+        if let Some(signing_key) = &self.signing_key {
+            let Some(expected_tag) = &expected_tag else {
+                return Err(Error::ArtifactTagMissing {
+                    hash: hash.to_owned(),
+                });
+            };
+
+            let computed_tag = Self::compute_artifact_tag(signing_key, team_id, hash, &body);
+            if !constant_time_eq(expected_tag.as_bytes(), computed_tag.as_bytes()) {
+                return Err(Error::ArtifactTagMismatch {
+                    expected: expected_tag.clone(),
+                    actual: computed_tag,
+                });
+            }
+        }
+
+        Ok(ArtifactResponse {
+            duration,
+            expected_tag,
+            body,
+        })
+    }
+
+    /// Uploads `artifact_body` as the cached artifact for `hash`. When a
+    /// signing key has been configured, attaches an `x-artifact-tag` header
+    /// so the server (or a later `fetch_artifact` call) can verify the
+    /// artifact wasn't corrupted or tampered with.
+    pub async fn upload_artifact(
+        &self,
+        hash: &str,
+        token: &str,
+        team_id: &str,
+        artifact_body: &[u8],
+    ) -> Result<(), Error> {
+        self.make_retryable_request(LimitType::Artifact, async || {
+            let mut request_builder = self
+                .client
+                .put(self.make_url(&format!("/v8/artifacts/{}", hash)))
+                .query(&[("teamId", team_id)])
+                .header("User-Agent", self.user_agent.clone())
+                .header("Content-Type", "application/octet-stream")
+                .header("Authorization", format!("Bearer {}", token));
+
+            if let Some(signing_key) = &self.signing_key {
+                let tag = Self::compute_artifact_tag(signing_key, team_id, hash, artifact_body);
+                request_builder = request_builder.header("x-artifact-tag", tag);
+            }
+
+            Ok(request_builder.body(artifact_body.to_vec()).send().await?)
+        })
+        .await?
+        .error_for_status()?;
+
+        Ok(())
+    }
+
+    /// `base64(HMAC_SHA256(key = signing_key, message = team_id ++ hash ++ body))`
+    fn compute_artifact_tag(signing_key: &[u8], team_id: &str, hash: &str, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(signing_key)
+            .expect("HMAC-SHA256 accepts keys of any length");
+        mac.update(team_id.as_bytes());
+        mac.update(hash.as_bytes());
+        mac.update(body);
+
+        base64::engine::general_purpose::STANDARD.encode(mac.finalize().into_bytes())
     }
 
-    const RETRY_MAX: u32 = 2;
+    /// Enables artifact tag signing/verification for `fetch_artifact` and
+    /// `upload_artifact`, keyed by the team's remote cache secret. Without
+    /// this, uploads carry no tag and downloads skip verification.
+    pub fn with_signing_key(mut self, secret: Vec<u8>) -> Self {
+        self.signing_key = Some(secret);
+        self
+    }
 
+    /// `retry_future` can send several real HTTP attempts for one call, so
+    /// capacity is acquired (and reconciled from the response headers) once
+    /// per attempt inside the closure rather than once per call - otherwise
+    /// a retry storm would spend several attempts' worth of quota while the
+    /// bucket accounting only ever saw one.
     async fn make_retryable_request<F: Future<Output = Result<reqwest::Response, Error>>>(
         &self,
+        limit_type: LimitType,
         request_builder: impl Fn() -> F,
     ) -> Result<reqwest::Response, Error> {
-        retry_future(Self::RETRY_MAX, request_builder, Self::should_retry_request).await
+        retry_future(
+            &self.retry_config,
+            || async {
+                self.rate_limiter.acquire(limit_type).await;
+
+                let response = request_builder().await;
+                if let Ok(response) = &response {
+                    self.rate_limiter.reconcile(limit_type, response.headers());
+                }
+
+                response
+            },
+            Self::should_retry_request,
+        )
+        .await
     }
 
+    /// Whether a failed request is worth retrying: only errors that carry a
+    /// 429 or 5xx (other than 501) status return true. Errors without a
+    /// status, such as connection failures, are not retried. Transient
+    /// *responses* (429/5xx) are handled directly inside `retry_future`,
+    /// which has access to the response and its headers.
     fn should_retry_request(error: &Error) -> bool {
         if let Error::ReqwestError(reqwest_error) = error {
             if let Some(status) = reqwest_error.status() {
@@ -273,10 +529,56 @@ impl APIClient {
             client,
             base_url: base_url.as_ref().to_string(),
             user_agent,
+            signing_key: None,
+            auth_state: Mutex::new(AuthState::default()),
+            rate_limiter: RateLimiter::default(),
+            retry_config: RetryConfig::default(),
         })
     }
 
+    /// Overrides the retry budget and backoff bounds used by every
+    /// retryable request. Defaults to 2 retries, a 100ms base delay, and a
+    /// 10s cap.
+    pub fn with_retry_config(mut self, retry_config: RetryConfig) -> Self {
+        self.retry_config = retry_config;
+        self
+    }
+
     fn make_url(&self, endpoint: &str) -> String {
         format!("{}{}", self.base_url, endpoint)
     }
 }
+
+/// Compares two byte slices in constant time, so that verifying an
+/// attacker-supplied artifact tag can't leak how many leading bytes matched
+/// through a timing side channel.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.iter().zip(b.iter()).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compute_artifact_tag_is_deterministic_and_input_sensitive() {
+        let key = b"signing-key".to_vec();
+        let tag = APIClient::compute_artifact_tag(&key, "team_123", "hash", b"body");
+
+        assert_eq!(tag, APIClient::compute_artifact_tag(&key, "team_123", "hash", b"body"));
+        assert_ne!(tag, APIClient::compute_artifact_tag(&key, "team_456", "hash", b"body"));
+        assert_ne!(tag, APIClient::compute_artifact_tag(&key, "team_123", "hash", b"other"));
+    }
+
+    #[test]
+    fn constant_time_eq_matches_naive_comparison() {
+        assert!(constant_time_eq(b"abc", b"abc"));
+        assert!(!constant_time_eq(b"abc", b"abd"));
+        assert!(!constant_time_eq(b"abc", b"ab"));
+        assert!(!constant_time_eq(b"", b"a"));
+    }
+}
@@ -0,0 +1,164 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use reqwest::header::HeaderMap;
+
+/// Which quota an endpoint draws from. Endpoints sharing a `LimitType` share
+/// rate-limit accounting even when they hit different URLs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum LimitType {
+    Auth,
+    Artifact,
+    Global,
+}
+
+#[derive(Debug, Clone)]
+struct Bucket {
+    remaining: u32,
+    reset_at: Instant,
+}
+
+impl Bucket {
+    /// Optimistic default for a bucket we haven't heard from yet: assume
+    /// capacity until a response tells us otherwise.
+    fn fresh() -> Self {
+        Self {
+            remaining: u32::MAX,
+            reset_at: Instant::now(),
+        }
+    }
+}
+
+/// Proactively throttles requests per [`LimitType`], instead of only
+/// reacting to a `429` after it happens.
+///
+/// `acquire` decrements the bucket optimistically and blocks until capacity
+/// is available (or the bucket's reset time has passed); `reconcile` then
+/// folds in the server's authoritative counts from the response's
+/// `X-RateLimit-Remaining` / `X-RateLimit-Reset` / `Retry-After` headers.
+/// This keeps concurrent task execution from hammering the cache API and
+/// smooths out 429 storms instead of retrying into them.
+#[derive(Default)]
+pub(crate) struct RateLimiter {
+    buckets: Mutex<HashMap<LimitType, Bucket>>,
+}
+
+impl RateLimiter {
+    pub(crate) async fn acquire(&self, limit_type: LimitType) {
+        loop {
+            let wait = {
+                let mut buckets = self.buckets.lock().unwrap();
+                let bucket = buckets.entry(limit_type).or_insert_with(Bucket::fresh);
+                let now = Instant::now();
+
+                if bucket.remaining == 0 && now < bucket.reset_at {
+                    Some(bucket.reset_at - now)
+                } else {
+                    if bucket.remaining == 0 {
+                        *bucket = Bucket::fresh();
+                    }
+                    bucket.remaining -= 1;
+                    None
+                }
+            };
+
+            match wait {
+                None => return,
+                Some(duration) => tokio::time::sleep(duration).await,
+            }
+        }
+    }
+
+    pub(crate) fn reconcile(&self, limit_type: LimitType, headers: &HeaderMap) {
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(limit_type).or_insert_with(Bucket::fresh);
+
+        if let Some(remaining) = header_num::<u32>(headers, "x-ratelimit-remaining") {
+            bucket.remaining = remaining;
+        }
+
+        if let Some(reset_unix) = header_num::<u64>(headers, "x-ratelimit-reset") {
+            let now_unix = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            bucket.reset_at = Instant::now() + Duration::from_secs(reset_unix.saturating_sub(now_unix));
+        }
+
+        if let Some(retry_after) = header_num::<u64>(headers, "retry-after") {
+            let candidate = Instant::now() + Duration::from_secs(retry_after);
+            bucket.reset_at = bucket.reset_at.max(candidate);
+            bucket.remaining = 0;
+        }
+    }
+}
+
+fn header_num<T: std::str::FromStr>(headers: &HeaderMap, name: &str) -> Option<T> {
+    headers.get(name)?.to_str().ok()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn acquire_does_not_block_a_fresh_bucket() {
+        let limiter = RateLimiter::default();
+        // A bucket that's never been reconciled is optimistic (`u32::MAX`
+        // remaining), so this must return immediately.
+        tokio::time::timeout(Duration::from_millis(100), limiter.acquire(LimitType::Artifact))
+            .await
+            .expect("acquire on a fresh bucket should not block");
+    }
+
+    #[test]
+    fn reconcile_adopts_remaining_and_reset_from_headers() {
+        let limiter = RateLimiter::default();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_static("0"));
+        let reset_unix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 30;
+        headers.insert("x-ratelimit-reset", HeaderValue::from(reset_unix));
+
+        limiter.reconcile(LimitType::Artifact, &headers);
+
+        let buckets = limiter.buckets.lock().unwrap();
+        let bucket = buckets.get(&LimitType::Artifact).unwrap();
+        assert_eq!(bucket.remaining, 0);
+        assert!(bucket.reset_at > Instant::now() + Duration::from_secs(20));
+    }
+
+    #[test]
+    fn reconcile_treats_retry_after_as_exhausting_the_bucket() {
+        let limiter = RateLimiter::default();
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", HeaderValue::from_static("30"));
+
+        limiter.reconcile(LimitType::Auth, &headers);
+
+        let buckets = limiter.buckets.lock().unwrap();
+        let bucket = buckets.get(&LimitType::Auth).unwrap();
+        assert_eq!(bucket.remaining, 0);
+        assert!(bucket.reset_at > Instant::now());
+    }
+
+    #[test]
+    fn limit_types_have_independent_buckets() {
+        let limiter = RateLimiter::default();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_static("0"));
+
+        limiter.reconcile(LimitType::Auth, &headers);
+
+        let buckets = limiter.buckets.lock().unwrap();
+        assert!(!buckets.contains_key(&LimitType::Artifact));
+    }
+}
@@ -0,0 +1,213 @@
+use std::{future::Future, time::Duration};
+
+use rand::Rng;
+use reqwest::{header::RETRY_AFTER, StatusCode};
+
+use crate::Error;
+
+/// Tunable knobs for [`retry_future`]'s backoff, so callers aren't stuck with
+/// one fixed retry budget.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryConfig {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Retries `request_builder` according to `config`, using full-jitter
+/// exponential backoff between attempts: attempt `n` sleeps a random
+/// duration in `[0, min(max_delay, base_delay * 2^n)]`. A `Retry-After`
+/// header on a transient response (seconds or an HTTP-date) clamps the sleep
+/// to at least that long, so we don't hammer a server that told us
+/// explicitly when to come back.
+pub async fn retry_future<F, Fut>(
+    config: &RetryConfig,
+    request_builder: F,
+    should_retry: fn(&Error) -> bool,
+) -> Result<reqwest::Response, Error>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<reqwest::Response, Error>>,
+{
+    let mut attempt = 0;
+
+    loop {
+        match request_builder().await {
+            Ok(response) => match transient_retry_after(&response) {
+                Some(_) if attempt >= config.max_retries => {
+                    return Err(Error::RetriesExhausted {
+                        attempts: attempt + 1,
+                        source: Box::new(Error::TransientStatus {
+                            status: response.status(),
+                        }),
+                    })
+                }
+                Some(retry_after) => {
+                    sleep_with_jitter(config, attempt, retry_after).await;
+                    attempt += 1;
+                }
+                None => return Ok(response),
+            },
+            Err(err) if attempt < config.max_retries && should_retry(&err) => {
+                sleep_with_jitter(config, attempt, None).await;
+                attempt += 1;
+            }
+            Err(err) => {
+                return Err(Error::RetriesExhausted {
+                    attempts: attempt + 1,
+                    source: Box::new(err),
+                })
+            }
+        }
+    }
+}
+
+/// `Some(retry_after)` if `response`'s status is transient (429, or 5xx
+/// other than 501 Not Implemented), carrying the parsed `Retry-After` value
+/// if the server sent one.
+fn transient_retry_after(response: &reqwest::Response) -> Option<Option<Duration>> {
+    let status = response.status();
+    let is_transient =
+        status == StatusCode::TOO_MANY_REQUESTS || (status.as_u16() >= 500 && status.as_u16() != 501);
+
+    if !is_transient {
+        return None;
+    }
+
+    Some(
+        response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after),
+    )
+}
+
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    // Fall back to the HTTP-date form (RFC 2822), e.g.
+    // "Wed, 21 Oct 2015 07:28:00 GMT".
+    let target = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    (target.with_timezone(&chrono::Utc) - chrono::Utc::now())
+        .to_std()
+        .ok()
+}
+
+async fn sleep_with_jitter(config: &RetryConfig, attempt: u32, retry_after_floor: Option<Duration>) {
+    let exp_delay = config
+        .base_delay
+        .saturating_mul(1u32 << attempt.min(31))
+        .min(config.max_delay);
+
+    let jittered = Duration::from_millis(rand::thread_rng().gen_range(0..=exp_delay.as_millis() as u64));
+    let delay = match retry_after_floor {
+        Some(floor) => jittered.max(floor),
+        None => jittered,
+    };
+
+    tokio::time::sleep(delay).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_retry_after_accepts_seconds() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+    }
+
+    #[test]
+    fn parse_retry_after_accepts_http_date() {
+        let future = chrono::Utc::now() + chrono::Duration::seconds(60);
+        let value = future.to_rfc2822();
+
+        let parsed = parse_retry_after(&value).expect("valid HTTP-date should parse");
+        // Allow a little slack for the time it takes this test to run.
+        assert!(parsed.as_secs() <= 60 && parsed.as_secs() >= 55);
+    }
+
+    #[test]
+    fn parse_retry_after_rejects_garbage() {
+        assert_eq!(parse_retry_after("not a date"), None);
+    }
+
+    #[tokio::test]
+    async fn sleep_with_jitter_respects_the_retry_after_floor() {
+        let config = RetryConfig {
+            max_retries: 2,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+        };
+
+        let start = tokio::time::Instant::now();
+        sleep_with_jitter(&config, 0, Some(Duration::from_millis(50))).await;
+        // The jittered exponential delay is capped at 5ms, but a Retry-After
+        // floor of 50ms must still win out.
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn sleep_with_jitter_stays_within_the_exponential_bound() {
+        let config = RetryConfig {
+            max_retries: 5,
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(40),
+        };
+
+        let start = tokio::time::Instant::now();
+        sleep_with_jitter(&config, 10, None).await;
+        // attempt=10 would overflow far past max_delay if not clamped.
+        assert!(start.elapsed() <= Duration::from_millis(100));
+    }
+
+    fn transient_response() -> reqwest::Response {
+        http::Response::builder()
+            .status(StatusCode::SERVICE_UNAVAILABLE)
+            .body(Vec::new())
+            .unwrap()
+            .into()
+    }
+
+    #[tokio::test]
+    async fn retry_future_surfaces_a_persistently_transient_response_as_retries_exhausted() {
+        let config = RetryConfig {
+            max_retries: 2,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(1),
+        };
+        let attempts = std::sync::atomic::AtomicU32::new(0);
+
+        let result = retry_future(
+            &config,
+            || async {
+                attempts.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                Ok(transient_response())
+            },
+            |_| false,
+        )
+        .await;
+
+        assert_eq!(attempts.load(std::sync::atomic::Ordering::SeqCst), 3);
+        match result {
+            Err(Error::RetriesExhausted { attempts, source }) => {
+                assert_eq!(attempts, 3);
+                assert!(matches!(*source, Error::TransientStatus { status } if status == StatusCode::SERVICE_UNAVAILABLE));
+            }
+            other => panic!("expected RetriesExhausted, got {other:?}"),
+        }
+    }
+}
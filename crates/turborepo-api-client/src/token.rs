@@ -0,0 +1,72 @@
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+/// How long before expiry we proactively refresh, so a request in flight
+/// doesn't race a token that expires mid-call.
+const REFRESH_SKEW: Duration = Duration::from_secs(60);
+
+/// OAuth2 access token response, returned by the token endpoint for both the
+/// client-credentials and device-code grants.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AccessToken {
+    pub token_type: String,
+    pub expires_in: u64,
+    pub access_token: String,
+}
+
+/// A cached access token plus the instant it expires at.
+#[derive(Debug, Clone)]
+pub(crate) struct CachedToken {
+    pub(crate) access_token: String,
+    expires_at: Instant,
+}
+
+impl CachedToken {
+    pub(crate) fn from_access_token(token: AccessToken) -> Self {
+        Self {
+            access_token: token.access_token,
+            expires_at: Instant::now() + Duration::from_secs(token.expires_in),
+        }
+    }
+
+    pub(crate) fn needs_refresh(&self) -> bool {
+        Instant::now() + REFRESH_SKEW >= self.expires_at
+    }
+}
+
+/// Credentials plus the last token acquired with them, so `ensure_token` can
+/// transparently refresh without asking the caller for the client id/secret
+/// on every request.
+#[derive(Default)]
+pub(crate) struct AuthState {
+    pub(crate) token: Option<CachedToken>,
+    pub(crate) credentials: Option<(String, String)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn needs_refresh_is_false_well_before_expiry() {
+        let token = CachedToken::from_access_token(AccessToken {
+            token_type: "bearer".to_owned(),
+            expires_in: 3600,
+            access_token: "abc".to_owned(),
+        });
+
+        assert!(!token.needs_refresh());
+    }
+
+    #[test]
+    fn needs_refresh_is_true_within_the_skew_window() {
+        let token = CachedToken::from_access_token(AccessToken {
+            token_type: "bearer".to_owned(),
+            expires_in: 1,
+            access_token: "abc".to_owned(),
+        });
+
+        assert!(token.needs_refresh());
+    }
+}
@@ -3,14 +3,61 @@ use std::f64::consts::PI;
 use console::{Style, StyledObject};
 use lazy_static::lazy_static;
 
+/// The level of color a terminal supports, inferred from `NO_COLOR`,
+/// `FORCE_COLOR`, `COLORTERM`, and `TERM`. Used to downgrade the Go-compatible
+/// 24-bit rainbow to whatever the terminal can actually render, instead of
+/// emitting truecolor escapes unconditionally.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorLevel {
+    None,
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+impl ColorLevel {
+    /// `NO_COLOR` disables color outright; an explicit numeric `FORCE_COLOR`
+    /// (`1`/`2`/`3`) pins a tier; otherwise we sniff `COLORTERM` (truecolor)
+    /// and `TERM` (`*-256color`, `dumb`), defaulting to 16-color support.
+    fn infer() -> Self {
+        if std::env::var_os("NO_COLOR").is_some() {
+            return ColorLevel::None;
+        }
+
+        if let Ok(force_color) = std::env::var("FORCE_COLOR") {
+            match force_color.as_str() {
+                "0" | "false" => return ColorLevel::None,
+                "1" => return ColorLevel::Ansi16,
+                "2" => return ColorLevel::Ansi256,
+                "3" | "true" => return ColorLevel::TrueColor,
+                _ => {}
+            }
+        }
+
+        if matches!(std::env::var("COLORTERM").as_deref(), Ok("truecolor") | Ok("24bit")) {
+            return ColorLevel::TrueColor;
+        }
+
+        match std::env::var("TERM").as_deref() {
+            Ok("dumb") => ColorLevel::None,
+            Ok(term) if term.ends_with("-256color") => ColorLevel::Ansi256,
+            _ => ColorLevel::Ansi16,
+        }
+    }
+}
+
 /// Helper struct to apply any necessary formatting to UI output
 pub struct UI {
     should_strip_ansi: bool,
+    color_level: ColorLevel,
 }
 
 impl UI {
     pub fn new(should_strip_ansi: bool) -> Self {
-        Self { should_strip_ansi }
+        Self {
+            should_strip_ansi,
+            color_level: ColorLevel::infer(),
+        }
     }
 
     /// Infer the color choice from environment variables and checking if stdout
@@ -25,7 +72,10 @@ impl UI {
                     _ => None,
                 });
         let should_strip_ansi = env_setting.unwrap_or_else(|| !atty::is(atty::Stream::Stdout));
-        Self { should_strip_ansi }
+        Self {
+            should_strip_ansi,
+            color_level: ColorLevel::infer(),
+        }
     }
 
     /// Apply the UI color mode to the given styled object
@@ -49,18 +99,79 @@ impl UI {
     }
 
     pub fn print_rainbow(&self, text: &str) {
-        if self.should_strip_ansi {
+        if self.should_strip_ansi || self.color_level == ColorLevel::None {
             println!("{}", text);
             return;
         }
         for (i, c) in text.char_indices() {
             let (r, g, b) = Self::rainbow_rgb(i);
-            print!("\x1b[1m\x1b[38;2;{};{};{}m{}\x1b[0m\x1b[0;1m", r, g, b, c);
+            match self.color_level {
+                ColorLevel::TrueColor => {
+                    print!("\x1b[1m\x1b[38;2;{};{};{}m{}\x1b[0m\x1b[0;1m", r, g, b, c);
+                }
+                ColorLevel::Ansi256 => {
+                    let idx = ansi256_index(r, g, b);
+                    print!("\x1b[1m\x1b[38;5;{}m{}\x1b[0m\x1b[0;1m", idx, c);
+                }
+                ColorLevel::Ansi16 => {
+                    let code = ansi16_code(r, g, b);
+                    print!("\x1b[1m\x1b[{}m{}\x1b[0m\x1b[0;1m", code, c);
+                }
+                ColorLevel::None => unreachable!("handled above"),
+            }
         }
         println!()
     }
 }
 
+/// The 16 base ANSI colors' approximate RGB values, paired with the SGR code
+/// that selects each as a foreground color.
+const ANSI16_COLORS: [(u8, (u8, u8, u8)); 16] = [
+    (30, (0, 0, 0)),
+    (31, (128, 0, 0)),
+    (32, (0, 128, 0)),
+    (33, (128, 128, 0)),
+    (34, (0, 0, 128)),
+    (35, (128, 0, 128)),
+    (36, (0, 128, 128)),
+    (37, (192, 192, 192)),
+    (90, (128, 128, 128)),
+    (91, (255, 0, 0)),
+    (92, (0, 255, 0)),
+    (93, (255, 255, 0)),
+    (94, (0, 0, 255)),
+    (95, (255, 0, 255)),
+    (96, (0, 255, 255)),
+    (97, (255, 255, 255)),
+];
+
+/// Quantizes an RGB triple to the nearest color in xterm's 6x6x6 color cube
+/// (indices 16-231 of the 256-color palette).
+fn ansi256_index(r: u8, g: u8, b: u8) -> u8 {
+    // xterm's cube levels are 0, 95, 135, 175, 215, 255; pick the closest.
+    let to_cube = |c: u8| match c {
+        0..=47 => 0,
+        48..=114 => 1,
+        _ => (c - 35) / 40,
+    };
+    16 + 36 * to_cube(r) + 6 * to_cube(g) + to_cube(b)
+}
+
+/// Finds the SGR code of the ANSI16 color closest to the given RGB triple by
+/// squared Euclidean distance.
+fn ansi16_code(r: u8, g: u8, b: u8) -> u8 {
+    ANSI16_COLORS
+        .iter()
+        .min_by_key(|(_, (cr, cg, cb))| {
+            let dr = r as i32 - *cr as i32;
+            let dg = g as i32 - *cg as i32;
+            let db = b as i32 - *cb as i32;
+            dr * dr + dg * dg + db * db
+        })
+        .map(|(code, _)| *code)
+        .expect("ANSI16_COLORS is non-empty")
+}
+
 lazy_static! {
     pub static ref GREY: Style = Style::new().dim();
 }
@@ -88,4 +199,42 @@ mod test {
         let grey_str = GREY.apply_to("gray");
         assert_eq!(format!("{}", ui.apply(grey_str)), "\u{1b}[2mgray\u{1b}[0m");
     }
+
+    #[test]
+    fn test_ansi256_index_quantizes_to_the_color_cube() {
+        assert_eq!(ansi256_index(0, 0, 0), 16);
+        assert_eq!(ansi256_index(255, 255, 255), 16 + 36 * 5 + 6 * 5 + 5);
+        assert_eq!(ansi256_index(255, 0, 0), 16 + 36 * 5);
+    }
+
+    #[test]
+    fn test_ansi16_code_finds_the_nearest_base_color() {
+        assert_eq!(ansi16_code(0, 0, 0), 30);
+        assert_eq!(ansi16_code(250, 5, 5), 91);
+        assert_eq!(ansi16_code(255, 255, 255), 97);
+    }
+
+    #[test]
+    fn test_color_level_infer_respects_no_color() {
+        std::env::set_var("NO_COLOR", "1");
+        std::env::remove_var("FORCE_COLOR");
+        assert_eq!(ColorLevel::infer(), ColorLevel::None);
+        std::env::remove_var("NO_COLOR");
+    }
+
+    #[test]
+    fn test_color_level_infer_honors_numeric_force_color() {
+        std::env::remove_var("NO_COLOR");
+        std::env::set_var("FORCE_COLOR", "2");
+        assert_eq!(ColorLevel::infer(), ColorLevel::Ansi256);
+        std::env::remove_var("FORCE_COLOR");
+    }
+
+    #[test]
+    fn test_ui_new_infers_color_level_rather_than_assuming_truecolor() {
+        std::env::remove_var("NO_COLOR");
+        std::env::set_var("FORCE_COLOR", "1");
+        assert_eq!(UI::new(false).color_level, ColorLevel::Ansi16);
+        std::env::remove_var("FORCE_COLOR");
+    }
 }
@@ -0,0 +1,96 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root
+ * directory of this source tree.
+ */
+
+//! A stable, platform-independent content hash for paths.
+//!
+//! `std::hash::Hash` makes no guarantee of stability across platforms or
+//! compiler versions and defines no wire format, which makes it unsuitable
+//! for turbo's remote cache keys: a path hashed on one machine must produce
+//! the same bytes when hashed on another.
+
+use crate::{
+    file_name::FileName,
+    project_relative_path::{AnchoredUnixPath, AnchoredUnixPathBuf},
+};
+
+/// Feeds a caller-provided hasher a length-prefixed, component-by-component
+/// encoding:
+///
+/// ```text
+/// u64 component_count (little-endian)
+/// for each component, in order:
+///     u64 component_len (little-endian)
+///     component bytes (UTF-8)
+/// ```
+///
+/// Length-prefixing each component (rather than feeding the components'
+/// bytes back to back) guarantees `foo/bar` and `foobar` never collide, and
+/// the fixed, documented scheme is reproducible across OSes regardless of
+/// the in-memory path separator.
+pub trait ContentHash {
+    fn content_hash<H: std::hash::Hasher>(&self, state: &mut H);
+}
+
+impl ContentHash for FileName {
+    fn content_hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        let bytes = self.as_str().as_bytes();
+        state.write(&(bytes.len() as u64).to_le_bytes());
+        state.write(bytes);
+    }
+}
+
+impl ContentHash for AnchoredUnixPath {
+    fn content_hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        let components: Vec<_> = self.iter().collect();
+        state.write(&(components.len() as u64).to_le_bytes());
+        for component in components {
+            component.content_hash(state);
+        }
+    }
+}
+
+impl ContentHash for AnchoredUnixPathBuf {
+    fn content_hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        (**self).content_hash(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::hash_map::DefaultHasher, hash::Hasher};
+
+    use super::ContentHash;
+    use crate::project_relative_path::AnchoredUnixPath;
+
+    fn hash(path: &AnchoredUnixPath) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        path.content_hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn component_boundary_prevents_collision() -> anyhow::Result<()> {
+        // A naive scheme that feeds the components' bytes back to back would
+        // collide here, since "foo" + "bar" and "foobar" are the same bytes.
+        assert_ne!(
+            hash(AnchoredUnixPath::new("foo/bar")?),
+            hash(AnchoredUnixPath::new("foobar")?),
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn hash_is_deterministic() -> anyhow::Result<()> {
+        let path = AnchoredUnixPath::new("packages/a/src/index.ts")?;
+        assert_eq!(hash(path), hash(path));
+
+        Ok(())
+    }
+}
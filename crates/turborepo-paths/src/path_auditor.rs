@@ -0,0 +1,178 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root
+ * directory of this source tree.
+ */
+
+//! Guards cache-restore extraction against a malicious or corrupt archive
+//! escaping the project root via symlinks.
+//!
+//! [`AnchoredUnixPath`] construction already rejects absolute paths and `..`
+//! components, but that alone isn't enough: an archive entry can still
+//! escape the root if an *intermediate* directory it traverses through is a
+//! symlink pointing outside the root. [`PathAuditor`] walks each prefix of a
+//! candidate path and checks for that before any IO happens.
+
+use std::{collections::HashSet, fs, sync::Mutex};
+
+use anyhow::{bail, Result};
+
+use crate::project::ProjectRoot;
+
+/// Verifies that joining a relative path onto a [`ProjectRoot`] can't
+/// resolve outside of it.
+///
+/// Already-audited prefixes are cached, so repeat checks across thousands of
+/// files in a restored archive only pay the `symlink_metadata` cost once per
+/// directory rather than once per file.
+pub struct PathAuditor {
+    root: ProjectRoot,
+    check_case_collisions: bool,
+    audited_prefixes: Mutex<HashSet<String>>,
+    case_folded_prefixes: Mutex<HashSet<String>>,
+}
+
+impl PathAuditor {
+    pub fn new(root: ProjectRoot) -> Self {
+        Self {
+            root,
+            check_case_collisions: false,
+            audited_prefixes: Mutex::new(HashSet::new()),
+            case_folded_prefixes: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Also reject paths that collide case-insensitively with a
+    /// previously-audited path (e.g. `Foo/x` after `foo/x`), for use when
+    /// extracting onto a case-insensitive filesystem.
+    pub fn with_case_collision_check(mut self) -> Self {
+        self.check_case_collisions = true;
+        self
+    }
+
+    /// Checks that every intermediate prefix of `path` is safe to traverse,
+    /// i.e. not a symlink that would carry resolution outside the project
+    /// root.
+    pub fn audit(&self, path: &str) -> Result<()> {
+        let mut prefix = String::new();
+
+        for component in path.split('/').filter(|c| !c.is_empty()) {
+            if component == ".." {
+                bail!("refusing to traverse \"{}\": `..` escapes the project root", path);
+            }
+            if !prefix.is_empty() {
+                prefix.push('/');
+            }
+            prefix.push_str(component);
+
+            if self.audited_prefixes.lock().unwrap().contains(&prefix) {
+                continue;
+            }
+
+            self.audit_prefix(&prefix)?;
+            self.audited_prefixes.lock().unwrap().insert(prefix.clone());
+        }
+
+        Ok(())
+    }
+
+    fn audit_prefix(&self, prefix: &str) -> Result<()> {
+        let resolved = self.root.root().join(prefix);
+
+        if let Ok(metadata) = fs::symlink_metadata(&resolved) {
+            if metadata.file_type().is_symlink() {
+                let target = fs::read_link(&resolved)?;
+                let target = if target.is_absolute() {
+                    target
+                } else {
+                    resolved
+                        .parent()
+                        .map(|parent| parent.join(&target))
+                        .unwrap_or(target)
+                };
+
+                let escapes = match target.canonicalize() {
+                    Ok(canonical) => !canonical.starts_with(self.root.root()),
+                    // Dangling symlink: can't canonicalize, so fall back to a
+                    // lexical check of the raw target.
+                    Err(_) => !target.starts_with(self.root.root()),
+                };
+
+                if escapes {
+                    bail!(
+                        "refusing to traverse \"{}\": symlink escapes the project root",
+                        prefix
+                    );
+                }
+            }
+        }
+
+        if self.check_case_collisions {
+            let folded = prefix.to_lowercase();
+            let mut case_folded_prefixes = self.case_folded_prefixes.lock().unwrap();
+            if !case_folded_prefixes.insert(folded) {
+                bail!(
+                    "refusing to extract \"{}\": collides case-insensitively with an \
+                     already-extracted path",
+                    prefix
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::*;
+    use crate::absolute_forward_system_path::AbsoluteForwardSystemPathBuf;
+
+    /// Creates a fresh, empty scratch directory under the system temp dir,
+    /// and the [`ProjectRoot`] rooted there.
+    fn scratch_root(name: &str) -> (PathBuf, ProjectRoot) {
+        let dir = std::env::temp_dir().join(format!("turbo-path-auditor-test-{name}"));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let root = ProjectRoot::new_unchecked(AbsoluteForwardSystemPathBuf::from(
+            dir.to_string_lossy().into_owned(),
+        ));
+        (dir, root)
+    }
+
+    #[test]
+    fn audit_allows_ordinary_nested_paths() {
+        let (_dir, root) = scratch_root("ordinary");
+        let auditor = PathAuditor::new(root);
+
+        assert!(auditor.audit("a/b/c").is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn audit_rejects_a_symlink_that_escapes_the_root() {
+        let (dir, root) = scratch_root("symlink-escape");
+        let outside_dir = std::env::temp_dir().join("turbo-path-auditor-test-outside");
+        let _ = fs::remove_dir_all(&outside_dir);
+        fs::create_dir_all(&outside_dir).unwrap();
+        std::os::unix::fs::symlink(&outside_dir, dir.join("escape")).unwrap();
+
+        let auditor = PathAuditor::new(root);
+
+        assert!(auditor.audit("escape/file.txt").is_err());
+    }
+
+    #[test]
+    fn audit_rejects_case_insensitive_collisions_when_enabled() {
+        let (_dir, root) = scratch_root("case-collision");
+        let auditor = PathAuditor::new(root).with_case_collision_check();
+
+        assert!(auditor.audit("Foo/x").is_ok());
+        assert!(auditor.audit("foo/x").is_err());
+    }
+}
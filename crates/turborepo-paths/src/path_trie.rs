@@ -0,0 +1,200 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root
+ * directory of this source tree.
+ */
+
+//! A radix-ish trie keyed by [`AnchoredUnixPath`] components.
+//!
+//! Turbo's large tracked file sets (hashing inputs, recording cache outputs)
+//! share long common prefixes; a flat `HashMap<AnchoredUnixPathBuf, V>`
+//! duplicates those prefixes per entry and can't answer "everything under
+//! directory X" without scanning every key. `PathTrie` stores one node per
+//! path component instead, so a directory's subtree can be located directly.
+
+use std::collections::{btree_map, BTreeMap};
+
+use crate::project_relative_path::{AnchoredUnixPath, AnchoredUnixPathBuf};
+
+struct Node<V> {
+    value: Option<V>,
+    children: BTreeMap<String, Node<V>>,
+}
+
+impl<V> Default for Node<V> {
+    fn default() -> Self {
+        Self {
+            value: None,
+            children: BTreeMap::new(),
+        }
+    }
+}
+
+/// A trie over [`AnchoredUnixPath`] components, supporting cheap "all
+/// entries under this directory" queries.
+pub struct PathTrie<V> {
+    root: Node<V>,
+}
+
+impl<V> Default for PathTrie<V> {
+    fn default() -> Self {
+        Self {
+            root: Node::default(),
+        }
+    }
+}
+
+impl<V> PathTrie<V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts `value` at `path`, returning the previous value if one was
+    /// already there.
+    pub fn insert(&mut self, path: &AnchoredUnixPath, value: V) -> Option<V> {
+        let mut node = &mut self.root;
+        for component in path.iter() {
+            node = node
+                .children
+                .entry(component.as_str().to_owned())
+                .or_default();
+        }
+        node.value.replace(value)
+    }
+
+    pub fn get(&self, path: &AnchoredUnixPath) -> Option<&V> {
+        let mut node = &self.root;
+        for component in path.iter() {
+            node = node.children.get(component.as_str())?;
+        }
+        node.value.as_ref()
+    }
+
+    /// Removes the value at `path`, pruning any now-empty ancestor nodes
+    /// left behind.
+    pub fn remove(&mut self, path: &AnchoredUnixPath) -> Option<V> {
+        fn remove_rec<V>(node: &mut Node<V>, components: &[String]) -> Option<V> {
+            let [first, rest @ ..] = components else {
+                return node.value.take();
+            };
+
+            let child = node.children.get_mut(first)?;
+            let removed = remove_rec(child, rest);
+
+            if removed.is_some() && child.value.is_none() && child.children.is_empty() {
+                node.children.remove(first);
+            }
+
+            removed
+        }
+
+        let components: Vec<String> = path.iter().map(|c| c.as_str().to_owned()).collect();
+        remove_rec(&mut self.root, &components)
+    }
+
+    /// Returns every entry whose path starts with `prefix`, in tree order.
+    /// An empty result means either the directory has no tracked entries or
+    /// doesn't exist in the trie - callers that need to distinguish those
+    /// should check `get` for an exact match first.
+    pub fn subtree(&self, prefix: &AnchoredUnixPath) -> Vec<(AnchoredUnixPathBuf, &V)> {
+        let mut node = &self.root;
+        for component in prefix.iter() {
+            match node.children.get(component.as_str()) {
+                Some(child) => node = child,
+                None => return Vec::new(),
+            }
+        }
+
+        PathTrieIter::new(node, prefix.as_str().to_owned()).collect()
+    }
+
+    /// Iterates over every entry in tree order.
+    pub fn iter(&self) -> PathTrieIter<'_, V> {
+        PathTrieIter::new(&self.root, String::new())
+    }
+}
+
+/// Iterates a [`PathTrie`] in tree order (a directory's entries grouped
+/// together, with a directory immediately followed by its children, and
+/// siblings in lexicographic order courtesy of the `BTreeMap` children).
+///
+/// Rust iterators can't recurse, so traversal is driven by an explicit stack
+/// of `(path prefix, child iterator)` frames - push one when descending into
+/// a child, pop and resume the parent's iterator once its children are
+/// exhausted.
+pub struct PathTrieIter<'a, V> {
+    stack: Vec<(String, btree_map::Iter<'a, String, Node<V>>)>,
+    pending: Option<(String, &'a V)>,
+}
+
+impl<'a, V> PathTrieIter<'a, V> {
+    fn new(root: &'a Node<V>, base: String) -> Self {
+        let pending = root.value.as_ref().map(|value| (base.clone(), value));
+        Self {
+            stack: vec![(base, root.children.iter())],
+            pending,
+        }
+    }
+}
+
+impl<'a, V> Iterator for PathTrieIter<'a, V> {
+    type Item = (AnchoredUnixPathBuf, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((prefix, value)) = self.pending.take() {
+            return Some((AnchoredUnixPathBuf::unchecked_new(prefix), value));
+        }
+
+        while let Some((prefix, iter)) = self.stack.last_mut() {
+            let Some((name, child)) = iter.next() else {
+                self.stack.pop();
+                continue;
+            };
+
+            let child_path = if prefix.is_empty() {
+                name.clone()
+            } else {
+                format!("{prefix}/{name}")
+            };
+
+            if let Some(value) = &child.value {
+                self.pending = Some((child_path.clone(), value));
+            }
+            self.stack.push((child_path, child.children.iter()));
+
+            if let Some((prefix, value)) = self.pending.take() {
+                return Some((AnchoredUnixPathBuf::unchecked_new(prefix), value));
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iter_visits_entries_in_tree_order() {
+        let mut trie = PathTrie::new();
+        trie.insert(AnchoredUnixPath::unchecked_new("foobar"), 1);
+        trie.insert(AnchoredUnixPath::unchecked_new("foo/bar"), 2);
+        trie.insert(AnchoredUnixPath::unchecked_new("foo"), 3);
+
+        let paths: Vec<String> = trie
+            .iter()
+            .map(|(path, _)| path.as_str().to_owned())
+            .collect();
+
+        // `foo` precedes its child `foo/bar`, and `foobar` - a lexicographically
+        // greater sibling at the root - comes last, even though a byte-order
+        // sort of the raw strings would put "foo" < "foo/bar" < "foobar" only
+        // by accident; a plain `HashMap` would instead yield these three in an
+        // arbitrary order.
+        assert_eq!(paths, vec!["foo", "foo/bar", "foobar"]);
+    }
+}
@@ -76,7 +76,7 @@ use crate::{
 
 /// A un-owned forward pointing, fully normalized path that is relative to the
 /// project root.
-#[derive(derive_more::Display, Derivative, Hash, PartialEq, Eq, PartialOrd, Ord, RefCast)]
+#[derive(derive_more::Display, Derivative, Hash, PartialEq, Eq, RefCast)]
 #[derivative(Debug)]
 #[repr(transparent)]
 pub struct AnchoredUnixPath(
@@ -87,12 +87,44 @@ pub struct AnchoredUnixPath(
 /// The owned version of the 'AnchoredUnixPath'
 #[derive(Clone, derive_more::Display, Derivative)]
 // split in two because formatters don't agree
-#[derive(Hash, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[derive(Hash, PartialEq, Eq, Serialize)]
 #[derivative(Debug)]
 pub struct AnchoredUnixPathBuf(
     #[derivative(Debug(format_with = "quoted_display"))] RelativeForwardUnixPathBuf,
 );
 
+/// Orders paths the way a directory traversal would visit them:
+/// component-by-component via [`AnchoredUnixPath::iter`], rather than by raw
+/// byte value. A strict prefix always sorts first (`foo` < `foo/bar`), and
+/// equal-length prefixes fall through to comparing the next component, so
+/// sibling components compare lexicographically (`foobar` > `foo/bar`,
+/// because the component `foobar` is greater than the component `foo`) -
+/// unlike the derived string-based `Ord`, which would put `foo.txt` before
+/// `foo/bar` since `.` (0x2E) sorts before `/` (0x2F).
+impl PartialOrd for AnchoredUnixPath {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for AnchoredUnixPath {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl PartialOrd for AnchoredUnixPathBuf {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for AnchoredUnixPathBuf {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        AnchoredUnixPath::cmp(self, other)
+    }
+}
+
 impl AsRef<RelativeForwardUnixPath> for AnchoredUnixPath {
     fn as_ref(&self) -> &RelativeForwardUnixPath {
         &self.0
@@ -245,6 +277,62 @@ impl AnchoredUnixPath {
         self.0.strip_prefix(&base.as_ref().0)
     }
 
+    /// Computes the path from `base` to `self`, for display relative to an
+    /// arbitrary working directory rather than always anchored at the
+    /// project root.
+    ///
+    /// Shares the common prefix via `iter()`, then emits one `..` component
+    /// per remaining component of `base` followed by the remaining
+    /// components of `self`. Takes the `strip_prefix` fast path when `base`
+    /// is a true ancestor of `self`.
+    ///
+    /// ```
+    /// use turborepo_paths::project_relative_path::AnchoredUnixPath;
+    ///
+    /// let path = AnchoredUnixPath::new("packages/a/src/index.ts")?;
+    /// let base = AnchoredUnixPath::new("packages/b")?;
+    ///
+    /// assert_eq!(path.relative_to(base)?.as_str(), "../a/src/index.ts");
+    ///
+    /// let child = AnchoredUnixPath::new("packages/a/src")?;
+    /// assert_eq!(path.relative_to(child)?.as_str(), "index.ts");
+    ///
+    /// # anyhow::Ok(())
+    /// ```
+    pub fn relative_to(
+        &self,
+        base: &AnchoredUnixPath,
+    ) -> anyhow::Result<RelativeForwardUnixPathBuf> {
+        if let Ok(suffix) = self.strip_prefix(base) {
+            return Ok(suffix.to_owned());
+        }
+
+        let mut self_components = self.iter().peekable();
+        let mut base_components = base.iter().peekable();
+
+        while self_components.peek().is_some() && self_components.peek() == base_components.peek()
+        {
+            self_components.next();
+            base_components.next();
+        }
+
+        let mut result = String::new();
+        for _ in base_components {
+            if !result.is_empty() {
+                result.push('/');
+            }
+            result.push_str("..");
+        }
+        for component in self_components {
+            if !result.is_empty() {
+                result.push('/');
+            }
+            result.push_str(component.as_str());
+        }
+
+        RelativeForwardUnixPathBuf::try_from(result)
+    }
+
     /// Determines whether `base` is a prefix of `self`.
     ///
     /// ```
@@ -670,4 +758,45 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn path_orders_in_tree_order() -> anyhow::Result<()> {
+        // A strict prefix sorts first, even though `.` < `/` in byte order
+        // would otherwise put `foo.txt` before `foo/bar`.
+        assert!(AnchoredUnixPath::new("foo")? < AnchoredUnixPath::new("foo/bar")?);
+        assert!(AnchoredUnixPath::new("foo.txt")? > AnchoredUnixPath::new("foo/bar")?);
+
+        // A sibling component that is lexicographically greater sorts after,
+        // regardless of how many components the other path has.
+        assert!(AnchoredUnixPath::new("foobar")? > AnchoredUnixPath::new("foo/bar")?);
+
+        // Equal-length prefixes fall through to the next component.
+        assert!(AnchoredUnixPath::new("foo/a")? < AnchoredUnixPath::new("foo/b")?);
+        assert!(AnchoredUnixPath::new("a/z")? < AnchoredUnixPath::new("b/a")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn relative_to_inserts_dot_dot_components() -> anyhow::Result<()> {
+        let path = AnchoredUnixPath::new("packages/a/src/index.ts")?;
+
+        // Sibling directory: share the `packages` prefix, then climb out of
+        // `b` before descending into `a`.
+        let base = AnchoredUnixPath::new("packages/b")?;
+        assert_eq!(path.relative_to(base)?.as_str(), "../a/src/index.ts");
+
+        // `base` is an ancestor: no `..` components, same as `strip_prefix`.
+        let ancestor = AnchoredUnixPath::new("packages/a")?;
+        assert_eq!(path.relative_to(ancestor)?.as_str(), "src/index.ts");
+
+        // No shared prefix at all.
+        let unrelated = AnchoredUnixPath::new("apps/web")?;
+        assert_eq!(
+            path.relative_to(unrelated)?.as_str(),
+            "../../packages/a/src/index.ts"
+        );
+
+        Ok(())
+    }
 }
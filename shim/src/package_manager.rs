@@ -1,5 +1,6 @@
 use crate::paths::AbsolutePath;
 use anyhow::{anyhow, Result};
+use globset::{Glob, GlobSetBuilder};
 use serde::Deserialize;
 use std::fs;
 
@@ -10,37 +11,193 @@ struct PnpmWorkspaces {
 
 #[derive(Debug, Deserialize)]
 struct PackageJsonWorkspaces {
-    pub workspaces: Vec<String>,
+    pub workspaces: WorkspacesField,
 }
 
+/// npm/Yarn allow `workspaces` to be either a plain array of globs or an
+/// object form carrying `packages` (and, historically, `nohoist`).
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum WorkspacesField {
+    Array(Vec<String>),
+    Object {
+        packages: Vec<String>,
+        #[serde(default)]
+        #[allow(dead_code)]
+        nohoist: Vec<String>,
+    },
+}
+
+impl WorkspacesField {
+    fn into_packages(self) -> Vec<String> {
+        match self {
+            WorkspacesField::Array(packages) => packages,
+            WorkspacesField::Object { packages, .. } => packages,
+        }
+    }
+}
+
+/// Workspace globs split into inclusions and exclusions (entries prefixed
+/// with `!`, as npm/Yarn/pnpm all allow).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceGlobs {
+    pub inclusions: Vec<String>,
+    pub exclusions: Vec<String>,
+}
+
+impl WorkspaceGlobs {
+    fn from_raw(raw: Vec<String>) -> Self {
+        let mut inclusions = Vec::new();
+        let mut exclusions = Vec::new();
+
+        for glob in raw {
+            match glob.strip_prefix('!') {
+                Some(rest) => exclusions.push(rest.to_owned()),
+                None => inclusions.push(glob),
+            }
+        }
+
+        Self {
+            inclusions,
+            exclusions,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PackageJson {
+    #[serde(rename = "packageManager")]
+    package_manager: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PnpmLockfile {
+    #[serde(rename = "lockfileVersion")]
+    lockfile_version: serde_yaml::Value,
+}
+
+#[derive(Debug, PartialEq, Eq)]
 pub enum PackageManager {
-    #[allow(dead_code)]
     Berry,
     Npm,
     Pnpm,
-    #[allow(dead_code)]
     Pnpm6,
-    #[allow(dead_code)]
     Yarn,
 }
 
 impl PackageManager {
-    /// Returns a list of globs for the package workspace.
-    /// NOTE: We return a `Vec<PathBuf>` instead of a `GlobSet` because we
+    /// Detects the package manager in use at `root_path`.
+    ///
+    /// First honors an explicit `"packageManager"` field in the root
+    /// `package.json` (e.g. `"pnpm@8.6.0"`), then falls back to sniffing the
+    /// lockfile that's present. Returns an error if neither source yields an
+    /// answer, so callers can prompt the user instead of guessing.
+    pub fn detect(root_path: &AbsolutePath) -> Result<Self> {
+        if let Some(package_manager) = Self::detect_from_package_json(root_path)? {
+            return Ok(package_manager);
+        }
+
+        if let Some(package_manager) = Self::detect_from_lockfile(root_path)? {
+            return Ok(package_manager);
+        }
+
+        Err(anyhow!(
+            "Could not detect a package manager for {}. Turborepo supports npm, \
+             pnpm, and yarn; make sure a \"packageManager\" field or lockfile is present.",
+            root_path.display()
+        ))
+    }
+
+    fn detect_from_package_json(root_path: &AbsolutePath) -> Result<Option<Self>> {
+        let package_json_path = root_path.join("package.json");
+        if !package_json_path.exists() {
+            return Ok(None);
+        }
+
+        let package_json_text = fs::read_to_string(package_json_path)?;
+        let package_json: PackageJson = serde_json::from_str(&package_json_text)?;
+
+        let Some(package_manager) = package_json.package_manager else {
+            return Ok(None);
+        };
+
+        // The field is `<name>@<version>`; we only care about the name.
+        let name = package_manager
+            .split('@')
+            .next()
+            .ok_or_else(|| anyhow!("package.json: malformed \"packageManager\" field"))?;
+        let version = package_manager.splitn(2, '@').nth(1);
+
+        match name {
+            "npm" => Ok(Some(PackageManager::Npm)),
+            "pnpm" => Ok(Some(PackageManager::Pnpm)),
+            "yarn" => match version.and_then(|v| v.chars().next()) {
+                Some('1') | None => Ok(Some(PackageManager::Yarn)),
+                Some(_) => Ok(Some(PackageManager::Berry)),
+            },
+            _ => Err(anyhow!(
+                "package.json: unsupported packageManager \"{}\"",
+                package_manager
+            )),
+        }
+    }
+
+    fn detect_from_lockfile(root_path: &AbsolutePath) -> Result<Option<Self>> {
+        if root_path.join("pnpm-lock.yaml").exists() {
+            let lockfile_text = fs::read_to_string(root_path.join("pnpm-lock.yaml"))?;
+            let lockfile: PnpmLockfile = serde_yaml::from_str(&lockfile_text)?;
+            let is_pre_v6 = match &lockfile.lockfile_version {
+                serde_yaml::Value::Number(n) => n.as_f64().map(|v| v < 6.0).unwrap_or(false),
+                serde_yaml::Value::String(s) => s
+                    .split('.')
+                    .next()
+                    .and_then(|major| major.parse::<u32>().ok())
+                    .map(|major| major < 6)
+                    .unwrap_or(false),
+                _ => false,
+            };
+
+            return Ok(Some(if is_pre_v6 {
+                PackageManager::Pnpm6
+            } else {
+                PackageManager::Pnpm
+            }));
+        }
+
+        if root_path.join("yarn.lock").exists() {
+            let lockfile_text = fs::read_to_string(root_path.join("yarn.lock"))?;
+            return Ok(Some(if lockfile_text.contains("__metadata:") {
+                PackageManager::Berry
+            } else {
+                PackageManager::Yarn
+            }));
+        }
+
+        if root_path.join("package-lock.json").exists() {
+            return Ok(Some(PackageManager::Npm));
+        }
+
+        Ok(None)
+    }
+}
+
+impl PackageManager {
+    /// Returns the inclusion/exclusion globs for the package workspace.
+    /// NOTE: We return `WorkspaceGlobs` instead of a `GlobSet` because we
     /// may need to iterate through these globs and a `GlobSet` doesn't allow that.
     ///
     /// # Arguments
     ///
     /// * `root_path`:
     ///
-    /// returns: Result<Vec<PathBuf, Global>, Error>
+    /// returns: Result<WorkspaceGlobs, Error>
     ///
     /// # Examples
     ///
     /// ```
     ///
     /// ```
-    pub fn get_workspace_globs(&self, root_path: &AbsolutePath) -> Result<Vec<String>> {
+    pub fn get_workspace_globs(&self, root_path: &AbsolutePath) -> Result<WorkspaceGlobs> {
         match self {
             PackageManager::Pnpm | PackageManager::Pnpm6 => {
                 let workspace_yaml = fs::read_to_string(root_path.join("pnpm-workspace.yaml"))?;
@@ -48,27 +205,143 @@ impl PackageManager {
                 if workspaces.packages.is_empty() {
                     Err(anyhow!("pnpm-workspace.yaml: no packages found. Turborepo requires pnpm workspaces and thus packages to be defined in the root pnpm-workspace.yaml"))
                 } else {
-                    Ok(workspaces.packages)
+                    Ok(WorkspaceGlobs::from_raw(workspaces.packages))
                 }
             }
             PackageManager::Berry | PackageManager::Npm | PackageManager::Yarn => {
                 let package_json_text = fs::read_to_string(root_path.join("package.json"))?;
                 let package_json: PackageJsonWorkspaces = serde_json::from_str(&package_json_text)?;
+                let packages = package_json.workspaces.into_packages();
 
-                if package_json.workspaces.is_empty() {
+                if packages.is_empty() {
                     Err(anyhow!("package.json: no packages found. Turborepo requires pnpm workspaces and thus packages to be defined in the root package.json"))
                 } else {
-                    Ok(package_json.workspaces)
+                    Ok(WorkspaceGlobs::from_raw(packages))
+                }
+            }
+        }
+    }
+
+    /// Walks up from `start` to find the enclosing workspace root, the way
+    /// Cargo's ancestor-walking workspace-root search does for `Cargo.toml`.
+    ///
+    /// A candidate directory qualifies if it has the manager's workspace
+    /// marker (`pnpm-workspace.yaml`, or `package.json` for npm/yarn/berry)
+    /// *and* its glob set actually covers `start`; this rules out a marker
+    /// left behind in an unrelated parent directory. When multiple enclosing
+    /// workspaces match (nested workspaces), the outermost one wins.
+    pub fn find_workspace_root(&self, start: &AbsolutePath) -> Result<AbsolutePath> {
+        let mut outermost: Option<AbsolutePath> = None;
+        let mut current = Some(start.to_owned());
+
+        while let Some(dir) = current {
+            if self.has_workspace_marker(&dir) {
+                if let Ok(globs) = self.get_workspace_globs(&dir) {
+                    if Self::globs_match(&dir, &globs, start)? {
+                        outermost = Some(dir.clone());
+                    }
                 }
             }
+
+            current = dir.parent().map(|parent| parent.to_owned());
+        }
+
+        outermost.ok_or_else(|| {
+            anyhow!(
+                "Could not find a {} workspace enclosing {}",
+                self.marker_name(),
+                start.display()
+            )
+        })
+    }
+
+    fn marker_name(&self) -> &'static str {
+        match self {
+            PackageManager::Pnpm | PackageManager::Pnpm6 => "pnpm-workspace.yaml",
+            PackageManager::Berry | PackageManager::Npm | PackageManager::Yarn => "package.json",
         }
     }
+
+    fn has_workspace_marker(&self, dir: &AbsolutePath) -> bool {
+        dir.join(self.marker_name()).exists()
+    }
+
+    fn globs_match(root: &AbsolutePath, globs: &WorkspaceGlobs, start: &AbsolutePath) -> Result<bool> {
+        let relative = match start.strip_prefix(root) {
+            Ok(relative) => relative,
+            Err(_) => return Ok(false),
+        };
+
+        if relative.as_os_str().is_empty() {
+            return Ok(true);
+        }
+
+        let build_set = |patterns: &[String]| -> Result<globset::GlobSet> {
+            let mut builder = GlobSetBuilder::new();
+            for glob in patterns {
+                builder.add(Glob::new(glob)?);
+            }
+            Ok(builder.build()?)
+        };
+
+        let included = build_set(&globs.inclusions)?.is_match(relative);
+        let excluded = build_set(&globs.exclusions)?.is_match(relative);
+
+        Ok(included && !excluded)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::Path;
+    use std::path::{Path, PathBuf};
+
+    /// Creates a fresh, empty scratch directory under the system temp dir for
+    /// a single test, so `PackageManager::detect` can be pointed at files
+    /// without touching the real repo.
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("turbo-package-manager-test-{name}"));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn detect_prefers_package_json_field_over_lockfile() {
+        let dir = scratch_dir("prefers-field");
+        fs::write(
+            dir.join("package.json"),
+            r#"{"packageManager": "pnpm@8.6.0"}"#,
+        )
+        .unwrap();
+        // A conflicting lockfile should be ignored since the field wins.
+        fs::write(dir.join("package-lock.json"), "{}").unwrap();
+
+        assert_eq!(PackageManager::detect(&dir).unwrap(), PackageManager::Pnpm);
+    }
+
+    #[test]
+    fn detect_falls_back_to_lockfile_sniffing() {
+        let dir = scratch_dir("lockfile-fallback");
+        fs::write(dir.join("yarn.lock"), "# THIS FILE IS AUTOGENERATED\n__metadata:\n  version: 6\n").unwrap();
+
+        assert_eq!(PackageManager::detect(&dir).unwrap(), PackageManager::Berry);
+    }
+
+    #[test]
+    fn detect_distinguishes_yarn_classic_from_berry() {
+        let dir = scratch_dir("yarn-classic");
+        fs::write(dir.join("yarn.lock"), "# THIS FILE IS AUTOGENERATED\n").unwrap();
+
+        assert_eq!(PackageManager::detect(&dir).unwrap(), PackageManager::Yarn);
+    }
+
+    #[test]
+    fn detect_errors_when_nothing_present() {
+        let dir = scratch_dir("nothing-present");
+
+        assert!(PackageManager::detect(&dir).is_err());
+    }
 
     #[test]
     fn test_get_workspace_globs() {
@@ -79,7 +352,68 @@ mod tests {
 
         assert_eq!(
             globs,
-            vec![String::from("apps/*"), String::from("packages/*")]
+            WorkspaceGlobs {
+                inclusions: vec![String::from("apps/*"), String::from("packages/*")],
+                exclusions: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test_workspaces_field_parses_object_form_and_negated_globs() {
+        let json: PackageJsonWorkspaces =
+            serde_json::from_str(r#"{"workspaces": {"packages": ["packages/*", "!packages/excluded"]}}"#)
+                .unwrap();
+
+        assert_eq!(
+            WorkspaceGlobs::from_raw(json.workspaces.into_packages()),
+            WorkspaceGlobs {
+                inclusions: vec![String::from("packages/*")],
+                exclusions: vec![String::from("packages/excluded")],
+            }
+        );
+    }
+
+    #[test]
+    fn test_workspaces_field_parses_array_form_with_negated_glob() {
+        let json: PackageJsonWorkspaces =
+            serde_json::from_str(r#"{"workspaces": ["packages/*", "!packages/excluded"]}"#).unwrap();
+
+        assert_eq!(
+            WorkspaceGlobs::from_raw(json.workspaces.into_packages()),
+            WorkspaceGlobs {
+                inclusions: vec![String::from("packages/*")],
+                exclusions: vec![String::from("packages/excluded")],
+            }
         );
     }
+
+    #[test]
+    fn find_workspace_root_locates_the_enclosing_root() {
+        let root = scratch_dir("find-workspace-root");
+        fs::write(
+            root.join("package.json"),
+            r#"{"workspaces": ["packages/*"]}"#,
+        )
+        .unwrap();
+        let package_dir = root.join("packages").join("a");
+        fs::create_dir_all(&package_dir).unwrap();
+
+        let found = PackageManager::Npm.find_workspace_root(&package_dir).unwrap();
+        assert_eq!(found, root);
+    }
+
+    #[test]
+    fn find_workspace_root_ignores_a_marker_whose_globs_dont_cover_start() {
+        let root = scratch_dir("find-workspace-root-mismatch");
+        fs::write(
+            root.join("package.json"),
+            r#"{"workspaces": ["apps/*"]}"#,
+        )
+        .unwrap();
+        let unrelated_dir = root.join("tools").join("script");
+        fs::create_dir_all(&unrelated_dir).unwrap();
+
+        assert!(PackageManager::Npm.find_workspace_root(&unrelated_dir).is_err());
+    }
 }
@@ -0,0 +1,356 @@
+use std::{
+    collections::HashMap,
+    fs,
+};
+
+use anyhow::{anyhow, Result};
+use globset::{Glob, GlobSetBuilder};
+use serde::Deserialize;
+
+use crate::{
+    package_manager::{PackageManager, WorkspaceGlobs},
+    paths::AbsolutePath,
+};
+
+/// Name of a workspace package, as declared in its `package.json` `"name"`
+/// field.
+pub type PackageName = String;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PackageJsonManifest {
+    name: String,
+    #[serde(default)]
+    dependencies: HashMap<String, String>,
+    #[serde(default)]
+    dev_dependencies: HashMap<String, String>,
+    #[serde(default)]
+    peer_dependencies: HashMap<String, String>,
+    #[serde(default)]
+    optional_dependencies: HashMap<String, String>,
+}
+
+/// A single package in the workspace graph.
+#[derive(Debug, Clone)]
+pub struct PackageNode {
+    pub name: PackageName,
+    pub package_dir: AbsolutePath,
+    /// Siblings resolved via a `workspace:` (or path) specifier.
+    pub internal_deps: Vec<PackageName>,
+    /// Everything else: `(name, version_req)` pulled from the registry.
+    pub external_deps: Vec<(String, String)>,
+}
+
+/// The set of packages in a monorepo, expanded from `PackageManager`'s glob
+/// set, along with the internal `workspace:` dependency edges between them.
+#[derive(Debug)]
+pub struct Workspace {
+    packages: HashMap<PackageName, PackageNode>,
+}
+
+/// Whether a dependency version specifier points at a sibling package by
+/// path rather than a registry version, per the pre-`workspace:`-protocol
+/// convention (`file:../a`, `../a`) or the even older bare `"*"` wildcard.
+fn is_local_specifier(version: &str) -> bool {
+    version == "*" || version.starts_with("file:") || version.starts_with('.')
+}
+
+impl Workspace {
+    /// Expands `package_manager`'s workspace globs under `root` into real
+    /// packages and links their internal `workspace:` dependencies.
+    ///
+    /// Directories matched by a glob but missing a `package.json` are
+    /// skipped rather than treated as an error; a `workspace:` specifier
+    /// that doesn't resolve to any package in the graph is a hard error,
+    /// since that's a broken reference rather than an external dependency.
+    pub fn new(package_manager: &PackageManager, root: &AbsolutePath) -> Result<Self> {
+        let globs = package_manager.get_workspace_globs(root)?;
+        let manifests = Self::read_manifests(root, &globs)?;
+        let packages = Self::link_internal_deps(manifests)?;
+
+        Ok(Self { packages })
+    }
+
+    fn read_manifests(
+        root: &AbsolutePath,
+        globs: &WorkspaceGlobs,
+    ) -> Result<HashMap<PackageName, (AbsolutePath, PackageJsonManifest)>> {
+        let mut exclude_builder = GlobSetBuilder::new();
+        for exclusion in &globs.exclusions {
+            exclude_builder.add(Glob::new(exclusion)?);
+        }
+        let exclude_set = exclude_builder.build()?;
+
+        let mut manifests = HashMap::new();
+        for inclusion in &globs.inclusions {
+            let pattern = root.join(inclusion);
+            for entry in glob::glob(&pattern.to_string_lossy())? {
+                let path = entry?;
+                if !path.is_dir() {
+                    continue;
+                }
+
+                let relative = path.strip_prefix(root)?;
+                if exclude_set.is_match(relative) {
+                    continue;
+                }
+
+                let package_json_path = path.join("package.json");
+                if !package_json_path.exists() {
+                    // An unmatched directory (no `package.json`) isn't a
+                    // package; skip it rather than failing the whole walk.
+                    continue;
+                }
+
+                let package_json_text = fs::read_to_string(&package_json_path)?;
+                let manifest: PackageJsonManifest = serde_json::from_str(&package_json_text)?;
+                manifests.insert(manifest.name.clone(), (AbsolutePath::from(path), manifest));
+            }
+        }
+
+        Ok(manifests)
+    }
+
+    fn link_internal_deps(
+        manifests: HashMap<PackageName, (AbsolutePath, PackageJsonManifest)>,
+    ) -> Result<HashMap<PackageName, PackageNode>> {
+        let mut packages = HashMap::new();
+
+        for (name, (package_dir, manifest)) in &manifests {
+            let mut internal_deps = Vec::new();
+            let mut external_deps = Vec::new();
+
+            let all_deps = manifest
+                .dependencies
+                .iter()
+                .chain(manifest.dev_dependencies.iter())
+                .chain(manifest.peer_dependencies.iter())
+                .chain(manifest.optional_dependencies.iter());
+
+            for (dep_name, version) in all_deps {
+                // The version suffix after `workspace:` (`^`, `~`, `1.2.3`,
+                // ...) plays no part in the lookup: the dependency's key is
+                // the name looked up, and only the presence of the
+                // `workspace:` protocol matters.
+                if version.starts_with("workspace:") {
+                    if !manifests.contains_key(dep_name) {
+                        return Err(anyhow!(
+                            "{} depends on \"{}\" via a workspace: specifier, but no such \
+                             package exists in the workspace",
+                            name,
+                            dep_name
+                        ));
+                    }
+                    internal_deps.push(dep_name.clone());
+                } else if is_local_specifier(version) && manifests.contains_key(dep_name) {
+                    // Pre-`workspace:`-protocol monorepos (older Yarn/npm/Lerna)
+                    // point at sibling packages with a `file:`/relative-path
+                    // specifier, or simply `"*"`. Only treat these as internal
+                    // when the name actually resolves to a workspace package,
+                    // since `"*"` and `file:` are also valid for genuine
+                    // external dependencies.
+                    internal_deps.push(dep_name.clone());
+                } else {
+                    external_deps.push((dep_name.clone(), version.clone()));
+                }
+            }
+
+            packages.insert(
+                name.clone(),
+                PackageNode {
+                    name: name.clone(),
+                    package_dir: package_dir.clone(),
+                    internal_deps,
+                    external_deps,
+                },
+            );
+        }
+
+        Ok(packages)
+    }
+
+    pub fn package(&self, name: &str) -> Option<&PackageNode> {
+        self.packages.get(name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.packages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.packages.is_empty()
+    }
+
+    /// Returns packages in topological order (a package's internal
+    /// dependencies always precede it), erroring out if the dependency
+    /// graph contains a cycle.
+    pub fn topological_order(&self) -> Result<Vec<&PackageNode>> {
+        enum Mark {
+            Visiting,
+            Done,
+        }
+
+        fn visit<'a>(
+            name: &str,
+            packages: &'a HashMap<PackageName, PackageNode>,
+            marks: &mut HashMap<PackageName, Mark>,
+            order: &mut Vec<&'a PackageNode>,
+        ) -> Result<()> {
+            match marks.get(name) {
+                Some(Mark::Done) => return Ok(()),
+                Some(Mark::Visiting) => {
+                    return Err(anyhow!(
+                        "dependency cycle detected in workspace, involving package \"{}\"",
+                        name
+                    ))
+                }
+                None => {}
+            }
+
+            marks.insert(name.to_owned(), Mark::Visiting);
+            let node = packages
+                .get(name)
+                .ok_or_else(|| anyhow!("internal dependency \"{}\" not found in workspace", name))?;
+            for dep in &node.internal_deps {
+                visit(dep, packages, marks, order)?;
+            }
+            marks.insert(name.to_owned(), Mark::Done);
+            order.push(node);
+
+            Ok(())
+        }
+
+        let mut marks = HashMap::new();
+        let mut order = Vec::new();
+
+        // Sort names before visiting so the resulting order is deterministic
+        // across runs.
+        let mut names: Vec<&PackageName> = self.packages.keys().collect();
+        names.sort();
+
+        for name in names {
+            visit(name, &self.packages, &mut marks, &mut order)?;
+        }
+
+        Ok(order)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn node(name: &str, internal_deps: &[&str]) -> PackageNode {
+        PackageNode {
+            name: name.to_owned(),
+            package_dir: PathBuf::from(name),
+            internal_deps: internal_deps.iter().map(|s| (*s).to_owned()).collect(),
+            external_deps: Vec::new(),
+        }
+    }
+
+    fn manifest(name: &str, dependencies: HashMap<String, String>) -> PackageJsonManifest {
+        PackageJsonManifest {
+            name: name.to_owned(),
+            dependencies,
+            dev_dependencies: HashMap::new(),
+            peer_dependencies: HashMap::new(),
+            optional_dependencies: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn topological_order_places_dependencies_before_dependents() {
+        let mut packages = HashMap::new();
+        packages.insert("a".to_owned(), node("a", &[]));
+        packages.insert("b".to_owned(), node("b", &["a"]));
+        packages.insert("c".to_owned(), node("c", &["a", "b"]));
+        let workspace = Workspace { packages };
+
+        let order: Vec<&str> = workspace
+            .topological_order()
+            .unwrap()
+            .into_iter()
+            .map(|node| node.name.as_str())
+            .collect();
+
+        assert_eq!(order, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn topological_order_detects_cycles() {
+        let mut packages = HashMap::new();
+        packages.insert("a".to_owned(), node("a", &["b"]));
+        packages.insert("b".to_owned(), node("b", &["a"]));
+        let workspace = Workspace { packages };
+
+        assert!(workspace.topological_order().is_err());
+    }
+
+    #[test]
+    fn link_internal_deps_classifies_workspace_protocol_as_internal() {
+        let mut manifests = HashMap::new();
+        manifests.insert("a".to_owned(), (PathBuf::from("a"), manifest("a", HashMap::new())));
+
+        let mut b_deps = HashMap::new();
+        b_deps.insert("a".to_owned(), "workspace:*".to_owned());
+        b_deps.insert("lodash".to_owned(), "^4.0.0".to_owned());
+        manifests.insert("b".to_owned(), (PathBuf::from("b"), manifest("b", b_deps)));
+
+        let packages = Workspace::link_internal_deps(manifests).unwrap();
+        let b = packages.get("b").unwrap();
+
+        assert_eq!(b.internal_deps, vec!["a".to_owned()]);
+        assert_eq!(
+            b.external_deps,
+            vec![("lodash".to_owned(), "^4.0.0".to_owned())]
+        );
+    }
+
+    #[test]
+    fn link_internal_deps_errors_on_unresolved_workspace_specifier() {
+        let mut deps = HashMap::new();
+        deps.insert("missing".to_owned(), "workspace:*".to_owned());
+
+        let mut manifests = HashMap::new();
+        manifests.insert("a".to_owned(), (PathBuf::from("a"), manifest("a", deps)));
+
+        assert!(Workspace::link_internal_deps(manifests).is_err());
+    }
+
+    #[test]
+    fn link_internal_deps_classifies_pre_workspace_protocol_specifiers_as_internal() {
+        let mut manifests = HashMap::new();
+        manifests.insert("a".to_owned(), (PathBuf::from("a"), manifest("a", HashMap::new())));
+
+        let mut b_deps = HashMap::new();
+        b_deps.insert("a".to_owned(), "file:../a".to_owned());
+        manifests.insert("b".to_owned(), (PathBuf::from("b"), manifest("b", b_deps)));
+
+        let mut c_deps = HashMap::new();
+        c_deps.insert("a".to_owned(), "*".to_owned());
+        manifests.insert("c".to_owned(), (PathBuf::from("c"), manifest("c", c_deps)));
+
+        let packages = Workspace::link_internal_deps(manifests).unwrap();
+
+        assert_eq!(packages.get("b").unwrap().internal_deps, vec!["a".to_owned()]);
+        assert_eq!(packages.get("c").unwrap().internal_deps, vec!["a".to_owned()]);
+    }
+
+    #[test]
+    fn link_internal_deps_treats_a_non_workspace_wildcard_as_external() {
+        let mut deps = HashMap::new();
+        deps.insert("lodash".to_owned(), "*".to_owned());
+
+        let mut manifests = HashMap::new();
+        manifests.insert("a".to_owned(), (PathBuf::from("a"), manifest("a", deps)));
+
+        let packages = Workspace::link_internal_deps(manifests).unwrap();
+        let a = packages.get("a").unwrap();
+
+        assert!(a.internal_deps.is_empty());
+        assert_eq!(a.external_deps, vec![("lodash".to_owned(), "*".to_owned())]);
+    }
+}